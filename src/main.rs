@@ -2,7 +2,7 @@ use std::{io::{BufRead, BufReader}, process::Command, time::Instant};
 
 use anyhow::Context;
 use chrono::{DateTime, Utc};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use serial2::{SerialPort, Settings};
 
 #[derive(Parser)]
@@ -13,26 +13,275 @@ struct Args {
 
     #[arg(short = 'r', long)]
     baud_rate: Option<u32>,
+
+    /// If no valid fix is read from the GPS device within `--fix-timeout` seconds, fall
+    /// back to querying this NTP server instead (e.g. `pool.ntp.org`).
+    #[arg(long)]
+    ntp_fallback: Option<String>,
+
+    /// How long to wait for a valid GPS fix before giving up and using `--ntp-fallback`.
+    #[arg(long, default_value_t = 30)]
+    fix_timeout: u64,
+
+    /// Treat `gps_device` as an AT-command cellular modem instead of an NMEA GPS stream,
+    /// and set the clock from the modem's NITZ network time (`AT+CCLK?`) instead.
+    #[arg(long)]
+    modem: bool,
+
+    /// After setting the system clock, also write it to the hardware RTC via
+    /// `RTC_SET_TIME`, so the time survives a reboot before any network time source is
+    /// available.
+    #[cfg(target_os = "linux")]
+    #[arg(long)]
+    sync_rtc: bool,
+
+    /// RTC device to write when `--sync-rtc` is set.
+    #[cfg(target_os = "linux")]
+    #[arg(long, default_value = "/dev/rtc0")]
+    rtc_device: String,
+
+    /// The hardware RTC runs in local time rather than UTC; convert before writing it.
+    #[cfg(target_os = "linux")]
+    #[arg(long)]
+    rtc_localtime: bool,
+
+    /// How to apply the time on Linux. `syscall` calls `settimeofday` directly, which
+    /// systemd-timesyncd/NTP may silently step back. `dbus` instead asks
+    /// systemd-timedated to disable NTP and set the clock, which sticks.
+    #[cfg(target_os = "linux")]
+    #[arg(long, value_enum, default_value_t = LinuxBackend::Syscall)]
+    backend: LinuxBackend,
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum LinuxBackend {
+    /// Set the clock directly via the `settimeofday` syscall.
+    Syscall,
+    /// Set the clock via `org.freedesktop.timedate1` over D-Bus.
+    Dbus,
+}
+
+/// Verifies the trailing `*HH` checksum of an NMEA sentence by XOR-ing every byte
+/// between `$` and `*`. Returns `false` if the sentence is malformed or lacks the
+/// `*HH` terminator entirely.
+fn verify_nmea_checksum(line: &str) -> bool {
+    let line = line.trim_end();
+    let Some(body) = line.strip_prefix('$') else {
+        return false;
+    };
+    let Some(star_index) = body.find('*') else {
+        return false;
+    };
+    let (payload, checksum_part) = body.split_at(star_index);
+    let checksum_str = &checksum_part[1..];
+    if checksum_str.len() != 2 {
+        return false;
+    }
+    let Ok(expected) = u8::from_str_radix(checksum_str, 16) else {
+        return false;
+    };
+    let actual = payload.bytes().fold(0u8, |acc, byte| acc ^ byte);
+
+    return actual == expected;
+}
+
+/// Dispatches to whichever OS-specific backend is compiled in to actually set the clock.
+fn apply_datetime(datetime: DateTime<Utc>, received_at: Instant, args: &Args) -> anyhow::Result<()> {
+    #[cfg(target_os = "macos")]
+    set_datetime_macos(datetime, received_at)?;
+    #[cfg(target_os = "linux")]
+    set_datetime_linux(datetime, received_at, args.backend)?;
+    #[cfg(target_os = "windows")]
+    set_datetime_windows(datetime, received_at)?;
+
+    #[cfg(target_os = "linux")]
+    if args.sync_rtc {
+        sync_rtc(datetime, received_at, args)?;
+    }
+
+    return Ok(());
+}
+
+const NTP_UNIX_EPOCH_OFFSET_SECS: i64 = 2_208_988_800;
+
+/// Queries `server` with a minimal SNTP client and returns the corrected current time
+/// along with the `Instant` it was computed at, so callers can compensate for any
+/// further latency exactly like the GPS-derived times do.
+fn query_sntp(server: &str) -> anyhow::Result<(DateTime<Utc>, Instant)> {
+    use std::net::UdpSocket;
+
+    let socket = UdpSocket::bind("0.0.0.0:0").context("Couldn't bind a UDP socket for the NTP request")?;
+    socket
+        .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+        .context("Couldn't set a read timeout on the NTP socket")?;
+    socket
+        .connect((server, 123))
+        .with_context(|| format!("Couldn't resolve NTP server {server}"))?;
+
+    let mut request = [0u8; 48];
+    request[0] = 0b00_100_011; // LI = 0, VN = 4, Mode = 3 (client)
+
+    let t1 = unix_now_secs();
+    socket.send(&request).context("Couldn't send NTP request")?;
+
+    let mut response = [0u8; 48];
+    let response_len = socket.recv(&mut response).context("Couldn't receive NTP response")?;
+    let received_at = Instant::now();
+    let t4 = unix_now_secs();
+
+    if response_len < 48 {
+        anyhow::bail!("NTP response from {server} was truncated ({response_len} bytes)");
+    }
+    let stratum = response[1];
+    if stratum == 0 {
+        anyhow::bail!("NTP server {server} sent a kiss-of-death reply (stratum 0); refusing to use it");
+    }
+
+    let t2 = read_ntp_timestamp(&response[32..40]);
+    let t3 = read_ntp_timestamp(&response[40..48]);
+
+    let offset = ((t2 - t1) + (t3 - t4)) / 2.0;
+    let corrected = t4 + offset;
+
+    let datetime = DateTime::from_timestamp(corrected.floor() as i64, (corrected.fract() * 1e9) as u32)
+        .context("NTP server returned an invalid timestamp")?;
+
+    return Ok((datetime, received_at));
+}
+
+/// Returns the local wall clock as seconds since the Unix epoch.
+fn unix_now_secs() -> f64 {
+    return std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("system clock is before the Unix epoch")
+        .as_secs_f64();
+}
+
+/// Reads a 64-bit NTP timestamp (32-bit seconds since 1900 + 32-bit fraction) out of
+/// `bytes` and converts it to seconds since the Unix epoch.
+fn read_ntp_timestamp(bytes: &[u8]) -> f64 {
+    let seconds = u32::from_be_bytes(bytes[0..4].try_into().unwrap());
+    let fraction = u32::from_be_bytes(bytes[4..8].try_into().unwrap());
+
+    let unix_seconds = seconds as i64 - NTP_UNIX_EPOCH_OFFSET_SECS;
+    return unix_seconds as f64 + fraction as f64 * 2f64.powi(-32);
 }
 
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
-    let port = SerialPort::open(args.gps_device, |mut settings: Settings| {
+    let port = SerialPort::open(&args.gps_device, |mut settings: Settings| {
         if let Some(rate) = args.baud_rate {
             settings.set_baud_rate(rate)?;
         }
         return Ok(settings);
     })?;
+
+    if args.modem {
+        return run_modem_source(port, &args);
+    }
+
+    let fix_deadline = if args.ntp_fallback.is_some() {
+        port.set_read_timeout(std::time::Duration::from_secs(args.fix_timeout))
+            .context("Couldn't set a read timeout on the GPS device")?;
+        Some(Instant::now() + std::time::Duration::from_secs(args.fix_timeout))
+    } else {
+        None
+    };
+
     let mut port = BufReader::new(port);
 
     let mut line = String::new();
     let mut seen_gpgga = 0;
-    while let Ok(_bytes_read) = port.read_line(&mut line) {
+    let mut seen_gpgll = 0;
+    loop {
+        if let Some(deadline) = fix_deadline {
+            if Instant::now() >= deadline {
+                let server = args.ntp_fallback.as_deref().unwrap();
+                println!(
+                    "No valid fix from {} within {}s. Falling back to NTP server {}.",
+                    args.gps_device, args.fix_timeout, server
+                );
+                let (datetime, received_at) = query_sntp(server)?;
+                apply_datetime(datetime, received_at, &args)?;
+                println!("Successfully set time!");
+                break;
+            }
+        }
+
+        let read_result = port.read_line(&mut line);
+
+        let _bytes_read = match read_result {
+            Ok(bytes_read) => bytes_read,
+            Err(err) if err.kind() == std::io::ErrorKind::TimedOut || err.kind() == std::io::ErrorKind::WouldBlock => {
+                line.truncate(0);
+                continue;
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::InvalidData => {
+                // A corrupted/non-UTF-8 byte in this sentence; discard it like a failed
+                // checksum instead of killing the whole process over one bad line.
+                println!("Discarding sentence with invalid (non-UTF-8) data.");
+                line.truncate(0);
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+
         let received_at = Instant::now();
+
+        if !verify_nmea_checksum(&line) {
+            println!("Discarding sentence with invalid or missing checksum.");
+            line.truncate(0);
+            continue;
+        }
+
         let mut splitline = line.split(',');
         let header = splitline.next().context("Missing GPS header")?;
         match header {
+            "$GPRMC" | "$GNRMC" => {
+                let time_status: &str = splitline.next().context("Missing UTC time status")?;
+                let status = splitline.next().context("Missing status")?;
+                let _lat = splitline.next();
+                let _lat_dir = splitline.next();
+                let _lon = splitline.next();
+                let _lon_dir = splitline.next();
+                let _speed = splitline.next();
+                let _track = splitline.next();
+                let date_ddmmyy: &str = splitline.next().context("Missing date")?;
+
+                if status != "A" {
+                    println!("$GPRMC/$GNRMC reports no valid fix. Ignoring.");
+                    line.truncate(0);
+                    continue;
+                }
+
+                if time_status.len() != 9 {
+                    anyhow::bail!("Invalid time status length");
+                }
+                if date_ddmmyy.len() != 6 {
+                    anyhow::bail!("Invalid date length");
+                }
+
+                let hour = time_status[0..2].parse()?;
+                let minute = time_status[2..4].parse()?;
+                let second = time_status[4..6].parse()?;
+                let millis = time_status[7..9].parse::<u32>()? * 10u32;
+
+                let day = date_ddmmyy[0..2].parse()?;
+                let month = date_ddmmyy[2..4].parse()?;
+                let year = 2000 + date_ddmmyy[4..6].parse::<i32>()?;
+
+                let date = chrono::NaiveDate::from_ymd_opt(year, month, day).context("Invalid date received")?;
+                let time = chrono::NaiveTime::from_hms_milli_opt(hour, minute, second, millis).context("Invalid time received")?;
+                let datetime = chrono::NaiveDateTime::new(date, time).and_utc();
+
+                apply_datetime(datetime, received_at, &args)?;
+
+                println!("Successfully set time!");
+
+                break;
+            }
             "$GPZDA" | "$GNZDA" =>  {
                 let time_status: &str = splitline.next().context("Missing UTC time status")?;
                 let day = splitline.next().context("Missing day")?.parse()?;
@@ -52,10 +301,7 @@ fn main() -> anyhow::Result<()> {
                 let time = chrono::NaiveTime::from_hms_milli_opt(hour, minute, second, millis).context("Invalid time received")?;
                 let datetime = chrono::NaiveDateTime::new(date, time).and_utc();
 
-                #[cfg(target_os = "macos")]
-                set_datetime_macos(datetime, received_at)?;
-                #[cfg(target_os = "linux")]
-                set_datetime_linux(datetime, received_at)?;
+                apply_datetime(datetime, received_at, &args)?;
 
                 println!("Successfully set time!");
 
@@ -68,8 +314,48 @@ fn main() -> anyhow::Result<()> {
                     line.truncate(0);
                     continue;
                 }
-                println!("$GPZDA/$GNZDA not seen in 5 reports. Using UTC data from $GPGGA.");
+                println!("$GPZDA/$GNZDA/$GxRMC not seen in 5 reports. Using UTC data from $GPGGA.");
+                let time_status: &str = splitline.next().context("Missing UTC time status")?;
+                if time_status.len() != 9 {
+                    anyhow::bail!("Invalid time status length");
+                }
+                let hour = time_status[0..2].parse()?;
+                let minute = time_status[2..4].parse()?;
+                let second = time_status[4..6].parse()?;
+                let millis = time_status[7..9].parse::<u32>()? * 10u32;
+                let date = chrono::offset::Utc::now();
+                let time = chrono::NaiveTime::from_hms_milli_opt(hour, minute, second, millis).context("Invalid time received")?;
+                let datetime = date.with_time(time).unwrap();
+
+                apply_datetime(datetime, received_at, &args)?;
+
+                break;
+            }
+            "$GPGLL" | "$GNGLL" => {
+                let _lat = splitline.next();
+                let _lat_dir = splitline.next();
+                let _lon = splitline.next();
+                let _lon_dir = splitline.next();
                 let time_status: &str = splitline.next().context("Missing UTC time status")?;
+                let status = splitline.next().context("Missing status")?;
+
+                if status != "A" {
+                    println!("$GPGLL/$GNGLL reports no valid fix. Ignoring.");
+                    line.truncate(0);
+                    continue;
+                }
+
+                if time_status.len() != 9 {
+                    anyhow::bail!("Invalid time status length");
+                }
+
+                seen_gpgll += 1;
+                if seen_gpgll < 5 {
+                    println!("Skipping $GPGLL. Fallback in {} reports.", 5 - seen_gpgll);
+                    line.truncate(0);
+                    continue;
+                }
+                println!("$GPZDA/$GNZDA/$GxRMC not seen in 5 reports. Using UTC data from $GPGLL.");
                 let hour = time_status[0..2].parse()?;
                 let minute = time_status[2..4].parse()?;
                 let second = time_status[4..6].parse()?;
@@ -78,10 +364,7 @@ fn main() -> anyhow::Result<()> {
                 let time = chrono::NaiveTime::from_hms_milli_opt(hour, minute, second, millis).context("Invalid time received")?;
                 let datetime = date.with_time(time).unwrap();
 
-                #[cfg(target_os = "macos")]
-                set_datetime_macos(datetime, received_at)?;
-                #[cfg(target_os = "linux")]
-                set_datetime_linux(datetime, received_at)?;
+                apply_datetime(datetime, received_at, &args)?;
 
                 break;
             }
@@ -95,6 +378,106 @@ fn main() -> anyhow::Result<()> {
     return Ok(());
 }
 
+/// Drives `port` as an AT-command modem: enables automatic NITZ time-zone update, then
+/// queries and sets the clock from the network time it reports.
+fn run_modem_source(port: SerialPort, args: &Args) -> anyhow::Result<()> {
+    let mut port = BufReader::new(port);
+
+    send_at_command(&mut port, "AT+CTZU=1")?;
+
+    let received_at = Instant::now();
+    let cclk_line = query_cclk(&mut port)?;
+
+    let datetime = parse_cclk_response(&cclk_line)?;
+    apply_datetime(datetime, received_at, args)?;
+    println!("Successfully set time!");
+
+    return Ok(());
+}
+
+/// Sends `AT+CCLK?` and reads the response in one pass, capturing the `+CCLK:` data line
+/// while still watching for the terminating `OK`/`ERROR`, since the modem sends the data
+/// line before `OK` rather than in place of it.
+fn query_cclk(port: &mut BufReader<SerialPort>) -> anyhow::Result<String> {
+    use std::io::Write;
+
+    write!(port, "AT+CCLK?\r\n").context("Failed sending AT+CCLK? to modem")?;
+
+    let mut cclk_line = None;
+    let mut line = String::new();
+    loop {
+        line.truncate(0);
+        port.read_line(&mut line)
+            .context("Failed reading response to AT+CCLK?")?;
+        let trimmed = line.trim();
+        if trimmed.starts_with("+CCLK:") {
+            cclk_line = Some(trimmed.to_string());
+            continue;
+        }
+        if trimmed == "OK" {
+            return cclk_line.context("Modem replied OK to AT+CCLK? without a +CCLK: line");
+        }
+        if trimmed == "ERROR" {
+            anyhow::bail!("Modem returned ERROR for AT+CCLK?");
+        }
+    }
+}
+
+/// Writes `command` to the modem and consumes lines until it echoes back `OK`.
+fn send_at_command(port: &mut BufReader<SerialPort>, command: &str) -> anyhow::Result<()> {
+    use std::io::Write;
+
+    write!(port, "{command}\r\n").with_context(|| format!("Failed sending {command} to modem"))?;
+
+    let mut line = String::new();
+    loop {
+        line.truncate(0);
+        port.read_line(&mut line)
+            .with_context(|| format!("Failed reading response to {command}"))?;
+        let trimmed = line.trim();
+        if trimmed == "OK" {
+            return Ok(());
+        }
+        if trimmed == "ERROR" {
+            anyhow::bail!("Modem returned ERROR for {command}");
+        }
+    }
+}
+
+/// Parses a `+CCLK: "yy/MM/dd,hh:mm:ss±zz"` response into a UTC `DateTime`. The two-digit
+/// year is normalized to 20xx, and the trailing `±zz` is a UTC offset in quarter-hours
+/// that's subtracted back out to recover UTC.
+fn parse_cclk_response(line: &str) -> anyhow::Result<DateTime<Utc>> {
+    let open_quote = line.find('"').context("Missing opening quote in +CCLK response")?;
+    let rest = &line[open_quote + 1..];
+    let close_quote = rest.find('"').context("Missing closing quote in +CCLK response")?;
+    let payload = &rest[..close_quote];
+
+    let (date_part, time_part) = payload.split_once(',').context("Malformed +CCLK timestamp")?;
+
+    let mut date_fields = date_part.split('/');
+    let year = 2000 + date_fields.next().context("Missing year in +CCLK response")?.parse::<i32>()?;
+    let month = date_fields.next().context("Missing month in +CCLK response")?.parse()?;
+    let day = date_fields.next().context("Missing day in +CCLK response")?.parse()?;
+
+    let offset_index = time_part.find(['+', '-']).context("Missing UTC offset in +CCLK response")?;
+    let (hms_part, offset_part) = time_part.split_at(offset_index);
+    let offset_sign = if offset_part.starts_with('-') { -1 } else { 1 };
+    let offset_minutes = offset_sign * offset_part[1..].parse::<i64>()? * 15;
+
+    let mut hms_fields = hms_part.split(':');
+    let hour = hms_fields.next().context("Missing hour in +CCLK response")?.parse()?;
+    let minute = hms_fields.next().context("Missing minute in +CCLK response")?.parse()?;
+    let second = hms_fields.next().context("Missing second in +CCLK response")?.parse()?;
+
+    let date = chrono::NaiveDate::from_ymd_opt(year, month, day).context("Invalid date in +CCLK response")?;
+    let time = chrono::NaiveTime::from_hms_opt(hour, minute, second).context("Invalid time in +CCLK response")?;
+    let local_datetime = chrono::NaiveDateTime::new(date, time);
+    let utc_datetime = local_datetime - chrono::Duration::minutes(offset_minutes);
+
+    return Ok(utc_datetime.and_utc());
+}
+
 #[cfg(target_os = "macos")]
 /// Makes sure NTP is off while running `callback`
 fn ntp_off_macos() -> anyhow::Result<()> {
@@ -171,9 +554,24 @@ pub fn set_datetime_macos(datetime: DateTime<Utc>, received_at: Instant) -> anyh
 }
 
 #[cfg(target_os = "linux")]
-/// Sets the time on a linux machine. This function should theoretically work,
-/// but has not been tested.
-pub fn set_datetime_linux(datetime: DateTime<Utc>, received_at: Instant) -> anyhow::Result<()> {
+/// Sets the time on a linux machine, dispatching to whichever backend the user selected.
+pub fn set_datetime_linux(
+    datetime: DateTime<Utc>,
+    received_at: Instant,
+    backend: LinuxBackend,
+) -> anyhow::Result<()> {
+    match backend {
+        LinuxBackend::Syscall => set_datetime_linux_syscall(datetime, received_at),
+        LinuxBackend::Dbus => set_datetime_linux_dbus(datetime, received_at),
+    }
+}
+
+#[cfg(target_os = "linux")]
+/// Sets the time on a linux machine directly via the `settimeofday` syscall. This
+/// function should theoretically work, but has not been tested. Note that
+/// systemd-timesyncd/NTP may silently step the clock back afterwards; prefer the
+/// `dbus` backend when that matters.
+fn set_datetime_linux_syscall(datetime: DateTime<Utc>, received_at: Instant) -> anyhow::Result<()> {
     let datetime = datetime + received_at.elapsed();
     let timestamp = datetime.timestamp();
     let millis = datetime.timestamp_millis();
@@ -189,3 +587,163 @@ pub fn set_datetime_linux(datetime: DateTime<Utc>, received_at: Instant) -> anyh
         return Ok(());
     }
 }
+
+#[cfg(target_os = "linux")]
+/// Sets the time on a linux machine via `org.freedesktop.timedate1` over D-Bus. Unlike
+/// the raw syscall, this asks systemd-timedated to disable NTP first, so
+/// timesyncd doesn't immediately step the clock back.
+fn set_datetime_linux_dbus(datetime: DateTime<Utc>, received_at: Instant) -> anyhow::Result<()> {
+    use dbus::blocking::Connection;
+
+    let datetime = datetime + received_at.elapsed();
+    let usec_utc = datetime.timestamp_micros();
+
+    let conn = Connection::new_system().context("Couldn't connect to the system D-Bus")?;
+    let timedated = conn.with_proxy(
+        "org.freedesktop.timedate1",
+        "/org/freedesktop/timedate1",
+        std::time::Duration::from_secs(5),
+    );
+
+    timedated
+        .method_call::<(), _, _, _>("org.freedesktop.timedate1", "SetNTP", (false, false))
+        .map_err(map_timedated_error)
+        .context("Couldn't disable network time synchronization")?;
+
+    timedated
+        .method_call::<(), _, _, _>(
+            "org.freedesktop.timedate1",
+            "SetTime",
+            (usec_utc, false, false),
+        )
+        .map_err(map_timedated_error)
+        .context("Couldn't set Linux time via timedated")?;
+
+    return Ok(());
+}
+
+#[cfg(target_os = "linux")]
+/// Translates a `timedate1` D-Bus error into a message explaining why the time
+/// couldn't be set, since the raw D-Bus error name isn't self-explanatory.
+fn map_timedated_error(error: dbus::Error) -> anyhow::Error {
+    let Some(name) = error.name() else {
+        return error.into();
+    };
+    return match name {
+        "org.freedesktop.DBus.Error.ServiceUnknown" => anyhow::anyhow!(
+            "systemd-timedated is not available on this system (org.freedesktop.timedate1 not found): {error}"
+        ),
+        "org.freedesktop.DBus.Error.AccessDenied" => anyhow::anyhow!(
+            "Permission denied setting time via timedated. Try running as root: {error}"
+        ),
+        "org.freedesktop.timedate1.Error.AutomaticTimeSyncEnabled" => anyhow::anyhow!(
+            "Network time synchronization is enabled and cannot be overridden by timedated: {error}"
+        ),
+        _ => anyhow::anyhow!("Failed to set time via timedated: {error}"),
+    };
+}
+
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Default)]
+struct RtcTime {
+    tm_sec: i32,
+    tm_min: i32,
+    tm_hour: i32,
+    tm_mday: i32,
+    tm_mon: i32,
+    tm_year: i32,
+    tm_wday: i32,
+    tm_yday: i32,
+    tm_isdst: i32,
+}
+
+/// `RTC_SET_TIME`, i.e. `_IOW('p', 0x0a, struct rtc_time)` from `<linux/rtc.h>`.
+#[cfg(target_os = "linux")]
+const RTC_SET_TIME: libc::c_ulong = 0x4024_700a;
+
+#[cfg(target_os = "linux")]
+/// Writes `datetime` to the hardware RTC at `args.rtc_device` via the `RTC_SET_TIME`
+/// ioctl, so the time survives a reboot. Honors `--rtc-localtime` for RTCs that run in
+/// local time instead of UTC.
+fn sync_rtc(datetime: DateTime<Utc>, received_at: Instant, args: &Args) -> anyhow::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let datetime = datetime + received_at.elapsed();
+
+    let rtc_time = if args.rtc_localtime {
+        naive_to_rtc_time(datetime.with_timezone(&chrono::Local).naive_local())
+    } else {
+        naive_to_rtc_time(datetime.naive_utc())
+    };
+
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&args.rtc_device)
+        .with_context(|| format!("Couldn't open RTC device {}", args.rtc_device))?;
+
+    unsafe {
+        let result = libc::ioctl(file.as_raw_fd(), RTC_SET_TIME, &rtc_time);
+        if result == -1 {
+            return Err(std::io::Error::last_os_error())
+                .with_context(|| format!("Failed to set RTC time on {}", args.rtc_device));
+        }
+    }
+
+    println!("Synced hardware RTC at {}.", args.rtc_device);
+
+    return Ok(());
+}
+
+#[cfg(target_os = "linux")]
+/// Converts a naive date/time into the `struct rtc_time` fields the kernel expects:
+/// `tm_year` is years since 1900, and `tm_mon` is 0-based.
+fn naive_to_rtc_time(naive: chrono::NaiveDateTime) -> RtcTime {
+    use chrono::{Datelike, Timelike};
+
+    return RtcTime {
+        tm_sec: naive.second() as i32,
+        tm_min: naive.minute() as i32,
+        tm_hour: naive.hour() as i32,
+        tm_mday: naive.day() as i32,
+        tm_mon: naive.month0() as i32,
+        tm_year: naive.year() - 1900,
+        tm_wday: 0,
+        tm_yday: 0,
+        tm_isdst: 0,
+    };
+}
+
+#[cfg(target_os = "windows")]
+/// Sets the time on a Windows machine via the Win32 `SetSystemTime` API. `SetSystemTime`
+/// expects UTC, so the `datetime` is used as-is with no timezone conversion.
+pub fn set_datetime_windows(datetime: DateTime<Utc>, received_at: Instant) -> anyhow::Result<()> {
+    use chrono::{Datelike, Timelike};
+    use winapi::um::minwinbase::SYSTEMTIME;
+    use winapi::um::sysinfoapi::SetSystemTime;
+
+    let datetime = datetime + received_at.elapsed();
+
+    let system_time = SYSTEMTIME {
+        wYear: datetime.year() as u16,
+        wMonth: datetime.month() as u16,
+        wDayOfWeek: 0,
+        wDay: datetime.day() as u16,
+        wHour: datetime.hour() as u16,
+        wMinute: datetime.minute() as u16,
+        wSecond: datetime.second() as u16,
+        wMilliseconds: (datetime.nanosecond() / 1_000_000) as u16,
+    };
+
+    unsafe {
+        if SetSystemTime(&system_time) == 0 {
+            anyhow::bail!(
+                "Failed to set Windows system time (insufficient privilege?): {}",
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+
+    return Ok(());
+}